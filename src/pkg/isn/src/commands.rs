@@ -1,7 +1,137 @@
-use crate::error::Result;
+use crate::config::Config;
+use crate::database::Database;
+use crate::error::{IsnError, Result};
+use crate::fetch;
+use crate::package::{InstalledPackage, Package};
+use crate::parse::{self, InstallOutcome};
+use crate::resolver;
+use crate::ui;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn install(package: &str, yes: bool) -> Result<()> {
+fn open_database(config: &Config) -> Result<Database> {
+    if let Some(parent) = config.database_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let db = Database::new(&config.database_path)?;
+    db.init_schema()?;
+    Ok(db)
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Queries `apk info -R` for `name`'s direct dependencies.
+fn fetch_package_info(name: &str) -> Result<Package> {
+    let output = Command::new("apk").arg("info").arg("-R").arg(name).output()?;
+
+    if !output.status.success() {
+        return Err(IsnError::PackageNotFound(name.to_string()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let dependencies = stdout
+        .lines()
+        .skip(1)
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Ok(Package {
+        name: name.to_string(),
+        version: String::new(),
+        description: String::new(),
+        dependencies,
+        size: fetch_package_size(name).unwrap_or(0),
+        // apk doesn't expose a package's checksum via `apk info`; this
+        // stays empty until package metadata comes from a real index
+        // instead of the local apk db. `fetch::verify` falls back to a
+        // size-only check when `checksum` is empty rather than skipping
+        // verification outright.
+        checksum: String::new(),
+    })
+}
+
+/// Queries `apk info -s` for `name`'s installed size, in bytes.
+fn fetch_package_size(name: &str) -> Option<u64> {
+    let output = Command::new("apk").arg("info").arg("-s").arg(name).output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    lines.find(|line| line.contains("installed size"))?;
+    parse_size(lines.next()?.trim())
+}
+
+/// Parses an apk-style size like "1.8 MB" into a byte count.
+fn parse_size(text: &str) -> Option<u64> {
+    let mut parts = text.split_whitespace();
+    let value: f64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()?.to_uppercase().as_str() {
+        "B" => 1.0,
+        "KB" | "KIB" => 1024.0,
+        "MB" | "MIB" => 1024.0 * 1024.0,
+        "GB" | "GIB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((value * multiplier) as u64)
+}
+
+/// Crawls `apk info -R` starting from `requested` to build the dependency
+/// graph needed by the resolver.
+fn fetch_package_graph(requested: &[String]) -> Result<HashMap<String, Package>> {
+    let mut graph = HashMap::new();
+    let mut stack: Vec<String> = requested.to_vec();
+
+    while let Some(name) = stack.pop() {
+        if graph.contains_key(&name) {
+            continue;
+        }
+        let pkg = fetch_package_info(&name)?;
+        for dep in &pkg.dependencies {
+            if !graph.contains_key(dep) {
+                stack.push(dep.clone());
+            }
+        }
+        graph.insert(name, pkg);
+    }
+
+    Ok(graph)
+}
+
+/// Queries `apk list --installed` for the packages apk itself considers
+/// installed, independent of what isn's own database has recorded.
+fn apk_installed_packages() -> Vec<InstalledPackage> {
+    let output = Command::new("apk").arg("list").arg("--installed").output();
+
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse::parse_installed_list(&stdout)
+}
+
+fn installed_version(package: &str) -> Option<String> {
+    apk_installed_packages()
+        .into_iter()
+        .find(|pkg| pkg.name == package)
+        .map(|pkg| pkg.version)
+}
+
+pub fn install(config: &Config, package: &str, yes: bool) -> Result<()> {
     println!("Installing package: {}", package);
 
     // Check if running as root
@@ -13,35 +143,113 @@ pub fn install(package: &str, yes: bool) -> Result<()> {
 
     // Use apk as backend for now
     // TODO: Implement native package management in future versions
-    let mut cmd = Command::new("apk");
-    cmd.arg("add");
+    let requested = vec![package.to_string()];
+    let graph = fetch_package_graph(&requested)?;
+
+    let db = open_database(config)?;
+    let explicit_installed: HashSet<String> = db
+        .query_installed(true)?
+        .into_iter()
+        .map(|pkg| pkg.name)
+        .collect();
+    // The resolver needs to know what's actually installed on the machine,
+    // not just what isn's own (possibly empty, on a fresh isn db) database
+    // has recorded — otherwise a fresh isn db would treat every
+    // already-satisfied apk dependency as missing and resolve + re-add the
+    // whole closure.
+    let installed: HashSet<String> = apk_installed_packages()
+        .into_iter()
+        .map(|pkg| pkg.name)
+        .collect();
+
+    let plan = resolver::resolve(&requested, &graph, &installed)?;
+
+    // A requested package that's already installed transitively just needs
+    // its `explicit` flag flipped; it never goes through `apk add` again.
+    for name in requested.iter().filter(|name| installed.contains(*name)) {
+        if !explicit_installed.contains(name) {
+            db.record_install(&InstalledPackage {
+                name: name.clone(),
+                version: installed_version(name).unwrap_or_else(|| "unknown".to_string()),
+                installed_at: now_unix(),
+                explicit: true,
+            })?;
+            ui::success(&format!("Marked already-installed package '{}' as explicit", name));
+        }
+    }
 
-    if yes {
-        cmd.arg("--no-cache");
+    if plan.order.is_empty() {
+        ui::success(&format!(
+            "Package '{}' and its dependencies are already installed",
+            package
+        ));
+        return Ok(());
     }
 
-    cmd.arg(package);
+    if plan.order.len() > 1 {
+        println!("Resolved install order: {}", plan.order.join(", "));
+    }
 
-    println!("Executing: apk add {} {}", if yes { "--no-cache" } else { "" }, package);
+    if !ui::confirm(&format!("Install {} package(s)?", plan.order.len()), yes) {
+        println!("Aborted.");
+        return Ok(());
+    }
 
-    let output = cmd.output()?;
+    for name in &plan.order {
+        // Fetch and verify the archive from the configured mirrors before
+        // the backend touches it at all. apk's local db doesn't expose a
+        // package's checksum, so verification falls back to size-only in
+        // that case (see `fetch::verify`) rather than skipping entirely.
+        if let Some(pkg) = graph.get(name) {
+            fetch::fetch(config, pkg)?;
+        }
 
-    if output.status.success() {
-        println!("✓ Package '{}' installed successfully", package);
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Failed to install package '{}':", package);
-        eprintln!("{}", stderr);
-        std::process::exit(1);
+        let mut cmd = Command::new("apk");
+        cmd.arg("add");
+
+        if yes {
+            cmd.arg("--no-cache");
+        }
+
+        cmd.arg(name);
+
+        let spinner = ui::spinner(&format!("Installing {}...", name));
+        let output = cmd.output()?;
+        spinner.finish_and_clear();
+
+        match parse::classify_install(&output) {
+            InstallOutcome::Installed => {}
+            InstallOutcome::AlreadyInstalled => {
+                return Err(IsnError::PackageAlreadyInstalled(name.clone()));
+            }
+            InstallOutcome::Failed(stderr) => {
+                ui::error(&format!("Failed to install package '{}':", name));
+                ui::error(&stderr);
+                std::process::exit(1);
+            }
+        }
+
+        db.record_install(&InstalledPackage {
+            name: name.clone(),
+            version: installed_version(name).unwrap_or_else(|| "unknown".to_string()),
+            installed_at: now_unix(),
+            explicit: plan.explicit.contains(name),
+        })?;
+
+        if let Some(pkg) = graph.get(name) {
+            db.set_dependencies(name, &pkg.dependencies)?;
+        }
     }
+
+    ui::success(&format!("Package '{}' installed successfully", package));
+    Ok(())
 }
 
 fn is_root() -> bool {
     unsafe { libc::geteuid() == 0 }
 }
 
-pub fn remove(package: &str, yes: bool) -> Result<()> {
+pub fn remove(config: &Config, package: &str, yes: bool, purge: bool) -> Result<()> {
     println!("Removing package: {}", package);
 
     if !is_root() {
@@ -50,28 +258,97 @@ pub fn remove(package: &str, yes: bool) -> Result<()> {
         std::process::exit(1);
     }
 
-    let mut cmd = Command::new("apk");
-    cmd.arg("del");
+    if !ui::confirm(&format!("Remove package '{}'?", package), yes) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    remove_package(config, package)?;
+    ui::success(&format!("Package '{}' removed successfully", package));
+
+    if purge {
+        let db = open_database(config)?;
+        let orphans = db.orphaned_dependencies()?;
+
+        if orphans.is_empty() {
+            return Ok(());
+        }
+
+        println!("Orphaned dependencies: {}", orphans.join(", "));
 
-    if !yes {
-        // TODO: Add confirmation prompt
+        if !ui::confirm(
+            &format!("Remove {} orphaned package(s)?", orphans.len()),
+            yes,
+        ) {
+            println!("Aborted.");
+            return Ok(());
+        }
+
+        for name in &orphans {
+            remove_package(config, name)?;
+        }
+        ui::success(&format!("Removed {} orphaned package(s)", orphans.len()));
     }
 
-    cmd.arg(package);
+    Ok(())
+}
 
-    println!("Executing: apk del {}", package);
+/// Runs `apk del` for a single package and removes its database row.
+fn remove_package(config: &Config, name: &str) -> Result<()> {
+    let mut cmd = Command::new("apk");
+    cmd.arg("del");
+    cmd.arg(name);
 
+    let spinner = ui::spinner(&format!("Removing {}...", name));
     let output = cmd.output()?;
+    spinner.finish_and_clear();
 
-    if output.status.success() {
-        println!("✓ Package '{}' removed successfully", package);
-        Ok(())
-    } else {
+    if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Failed to remove package '{}':", package);
-        eprintln!("{}", stderr);
+        ui::error(&format!("Failed to remove package '{}':", name));
+        ui::error(&stderr);
         std::process::exit(1);
     }
+
+    let db = open_database(config)?;
+    db.mark_removed(name)?;
+
+    Ok(())
+}
+
+/// Removes installed packages that are no longer required by anything:
+/// not explicitly installed and not referenced by any other package's
+/// recorded dependencies.
+pub fn autoremove(config: &Config, yes: bool) -> Result<()> {
+    println!("Looking for orphaned dependencies...");
+
+    if !is_root() {
+        eprintln!("Error: Package removal requires root privileges");
+        eprintln!("Please run: sudo isn autoremove");
+        std::process::exit(1);
+    }
+
+    let db = open_database(config)?;
+    let orphans = db.orphaned_dependencies()?;
+
+    if orphans.is_empty() {
+        ui::success("No orphaned dependencies found");
+        return Ok(());
+    }
+
+    println!("Orphaned dependencies: {}", orphans.join(", "));
+
+    if !ui::confirm(&format!("Remove {} orphaned package(s)?", orphans.len()), yes) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    for name in &orphans {
+        remove_package(config, name)?;
+    }
+
+    ui::success(&format!("Removed {} orphaned package(s)", orphans.len()));
+    Ok(())
 }
 
 pub fn update() -> Result<()> {
@@ -86,19 +363,19 @@ pub fn update() -> Result<()> {
     let mut cmd = Command::new("apk");
     cmd.arg("update");
 
-    println!("Executing: apk update");
-
+    let spinner = ui::spinner("Updating package database...");
     let output = cmd.output()?;
+    spinner.finish_and_clear();
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
         print!("{}", stdout);
-        println!("✓ Package database updated successfully");
+        ui::success("Package database updated successfully");
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Failed to update package database:");
-        eprintln!("{}", stderr);
+        ui::error("Failed to update package database:");
+        ui::error(&stderr);
         std::process::exit(1);
     }
 }
@@ -112,6 +389,11 @@ pub fn upgrade(yes: bool) -> Result<()> {
         std::process::exit(1);
     }
 
+    if !ui::confirm("Upgrade all packages?", yes) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
     let mut cmd = Command::new("apk");
     cmd.arg("upgrade");
 
@@ -119,19 +401,19 @@ pub fn upgrade(yes: bool) -> Result<()> {
         cmd.arg("--no-cache");
     }
 
-    println!("Executing: apk upgrade");
-
+    let spinner = ui::spinner("Upgrading packages...");
     let output = cmd.output()?;
+    spinner.finish_and_clear();
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
         print!("{}", stdout);
-        println!("✓ Packages upgraded successfully");
+        ui::success("Packages upgraded successfully");
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Failed to upgrade packages:");
-        eprintln!("{}", stderr);
+        ui::error("Failed to upgrade packages:");
+        ui::error(&stderr);
         std::process::exit(1);
     }
 }
@@ -147,17 +429,25 @@ pub fn search(query: &str) -> Result<()> {
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
-        if stdout.trim().is_empty() {
+        let packages = parse::parse_search(&stdout);
+
+        if packages.is_empty() {
             println!("No packages found matching '{}'", query);
         } else {
             println!("Available packages:");
-            print!("{}", stdout);
+            for package in &packages {
+                if package.description.is_empty() {
+                    println!("{}-{}", package.name, package.version);
+                } else {
+                    println!("{}-{} - {}", package.name, package.version, package.description);
+                }
+            }
         }
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Search failed:");
-        eprintln!("{}", stderr);
+        ui::error("Search failed:");
+        ui::error(&stderr);
         std::process::exit(1);
     }
 }
@@ -173,35 +463,33 @@ pub fn info(package: &str) -> Result<()> {
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
-        print!("{}", stdout);
+        match parse::parse_info(&stdout) {
+            Some(pkg) => {
+                println!("Name: {}", pkg.name);
+                println!("Version: {}", pkg.version);
+                println!("Description: {}", pkg.description);
+            }
+            None => print!("{}", stdout),
+        }
         Ok(())
     } else {
-        eprintln!("Package '{}' not found", package);
+        ui::error(&format!("Package '{}' not found", package));
         std::process::exit(1);
     }
 }
 
-pub fn list(explicit: bool) -> Result<()> {
+pub fn list(config: &Config, explicit: bool) -> Result<()> {
     println!("Listing {} packages...", if explicit { "explicitly installed" } else { "all installed" });
 
-    let mut cmd = Command::new("apk");
-    cmd.arg("list");
-    cmd.arg("--installed");
-
-    let output = cmd.output()?;
+    let db = open_database(config)?;
+    let packages = db.query_installed(explicit)?;
 
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let lines: Vec<&str> = stdout.lines().collect();
-        println!("Total packages installed: {}", lines.len());
-        print!("{}", stdout);
-        Ok(())
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Failed to list packages:");
-        eprintln!("{}", stderr);
-        std::process::exit(1);
+    println!("Total packages installed: {}", packages.len());
+    for package in &packages {
+        println!("{}-{}", package.name, package.version);
     }
+
+    Ok(())
 }
 
 pub fn verify(package: &str) -> Result<()> {
@@ -211,15 +499,17 @@ pub fn verify(package: &str) -> Result<()> {
     cmd.arg("verify");
     cmd.arg(package);
 
+    let spinner = ui::spinner(&format!("Verifying {}...", package));
     let output = cmd.output()?;
+    spinner.finish_and_clear();
 
     if output.status.success() {
-        println!("✓ Package '{}' verified successfully", package);
+        ui::success(&format!("Package '{}' verified successfully", package));
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Verification failed for package '{}':", package);
-        eprintln!("{}", stderr);
+        ui::error(&format!("Verification failed for package '{}':", package));
+        ui::error(&stderr);
         std::process::exit(1);
     }
 }
@@ -233,14 +523,19 @@ pub fn security_update(yes: bool) -> Result<()> {
         std::process::exit(1);
     }
 
+    if !ui::confirm("Run security update (update + upgrade all packages)?", yes) {
+        println!("Aborted.");
+        return Ok(());
+    }
+
     // First update the package database
-    println!("Step 1: Updating package database...");
     let mut update_cmd = Command::new("apk");
     update_cmd.arg("update");
+    let spinner = ui::spinner("Step 1: Updating package database...");
     let _ = update_cmd.output()?;
+    spinner.finish_and_clear();
 
     // Then upgrade all packages
-    println!("Step 2: Upgrading all packages...");
     let mut upgrade_cmd = Command::new("apk");
     upgrade_cmd.arg("upgrade");
 
@@ -248,17 +543,19 @@ pub fn security_update(yes: bool) -> Result<()> {
         upgrade_cmd.arg("--no-cache");
     }
 
+    let spinner = ui::spinner("Step 2: Upgrading all packages...");
     let output = upgrade_cmd.output()?;
+    spinner.finish_and_clear();
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
         print!("{}", stdout);
-        println!("✓ Security update completed successfully");
+        ui::success("Security update completed successfully");
         Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("Security update failed:");
-        eprintln!("{}", stderr);
+        ui::error("Security update failed:");
+        ui::error(&stderr);
         std::process::exit(1);
     }
 }
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{IsnError, Result};
+
+const SYSTEM_CONFIG_PATH: &str = "/etc/isn/config.toml";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -19,3 +24,147 @@ impl Default for Config {
         }
     }
 }
+
+/// A partial `Config` as read from TOML, where any field may be absent.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    database_path: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    mirrors: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Loads the configuration, falling back to `Default` when no file is
+    /// present. With no `override_path`, `/etc/isn/config.toml` is read
+    /// first and then overlaid with `$XDG_CONFIG_HOME/isn/config.toml` (or
+    /// `~/.config/isn/config.toml`) if present. `override_path`, when
+    /// given, replaces this search with a single file that must exist.
+    pub fn load(override_path: Option<&Path>) -> Result<Config> {
+        let mut config = Config::default();
+
+        if let Some(path) = override_path {
+            let file = Self::read_file(path)?.ok_or_else(|| {
+                IsnError::ConfigError(format!("config file not found: {}", path.display()))
+            })?;
+            config.apply(file);
+            return Ok(config);
+        }
+
+        if let Some(file) = Self::read_file(Path::new(SYSTEM_CONFIG_PATH))? {
+            config.apply(file);
+        }
+
+        if let Some(user_path) = Self::user_config_path() {
+            if let Some(file) = Self::read_file(&user_path)? {
+                config.apply(file);
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn read_file(path: &Path) -> Result<Option<ConfigFile>> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map(Some)
+                .map_err(|err| IsnError::ConfigError(format!("{}: {}", path.display(), err))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(IsnError::IoError(err)),
+        }
+    }
+
+    fn apply(&mut self, file: ConfigFile) {
+        if let Some(database_path) = file.database_path {
+            self.database_path = database_path;
+        }
+        if let Some(cache_dir) = file.cache_dir {
+            self.cache_dir = cache_dir;
+        }
+        if let Some(mirrors) = file.mirrors {
+            self.mirrors = mirrors;
+        }
+    }
+
+    fn user_config_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("isn").join("config.toml"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("isn-config-test-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn read_file_returns_none_when_missing() {
+        let path = temp_path("missing");
+        assert!(Config::read_file(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn read_file_reports_malformed_toml_as_config_error() {
+        let path = temp_path("malformed");
+        fs::write(&path, "not = [valid").unwrap();
+
+        let err = Config::read_file(&path).unwrap_err();
+        fs::remove_file(&path).ok();
+
+        match err {
+            IsnError::ConfigError(_) => {}
+            other => panic!("expected ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_file_parses_only_the_fields_present() {
+        let path = temp_path("partial");
+        fs::write(&path, "cache_dir = \"/tmp/isn-cache\"\n").unwrap();
+
+        let file = Config::read_file(&path).unwrap().unwrap();
+        fs::remove_file(&path).ok();
+
+        assert_eq!(file.cache_dir, Some(PathBuf::from("/tmp/isn-cache")));
+        assert!(file.database_path.is_none());
+        assert!(file.mirrors.is_none());
+    }
+
+    #[test]
+    fn apply_only_overrides_fields_present_in_the_file() {
+        let mut config = Config::default();
+        let default_mirrors = config.mirrors.clone();
+
+        config.apply(ConfigFile {
+            database_path: Some(PathBuf::from("/srv/isn/db.sqlite")),
+            cache_dir: None,
+            mirrors: None,
+        });
+
+        assert_eq!(config.database_path, PathBuf::from("/srv/isn/db.sqlite"));
+        assert_eq!(config.cache_dir, Config::default().cache_dir);
+        assert_eq!(config.mirrors, default_mirrors);
+    }
+
+    #[test]
+    fn later_apply_calls_override_earlier_ones() {
+        let mut config = Config::default();
+
+        config.apply(ConfigFile {
+            database_path: Some(PathBuf::from("/first")),
+            cache_dir: None,
+            mirrors: None,
+        });
+        config.apply(ConfigFile {
+            database_path: Some(PathBuf::from("/second")),
+            cache_dir: None,
+            mirrors: None,
+        });
+
+        assert_eq!(config.database_path, PathBuf::from("/second"));
+    }
+}
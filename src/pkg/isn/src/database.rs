@@ -1,11 +1,15 @@
-use rusqlite::{Connection, Result};
+use rusqlite::{params, Connection, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::package::InstalledPackage;
 
 pub struct Database {
     conn: Connection,
 }
 
 impl Database {
-    pub fn new(path: &str) -> Result<Self> {
+    pub fn new(path: &Path) -> Result<Self> {
         let conn = Connection::open(path)?;
         Ok(Self { conn })
     }
@@ -18,7 +22,8 @@ impl Database {
                 name TEXT NOT NULL UNIQUE,
                 version TEXT NOT NULL,
                 description TEXT,
-                installed_at INTEGER
+                installed_at INTEGER,
+                explicit INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS dependencies (
@@ -32,4 +37,224 @@ impl Database {
         )?;
         Ok(())
     }
+
+    /// Records a successful install (or re-install) of `package`, overwriting
+    /// any previous row for the same name.
+    pub fn record_install(&self, package: &InstalledPackage) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO packages (name, version, installed_at, explicit)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET
+                version = excluded.version,
+                installed_at = excluded.installed_at,
+                explicit = excluded.explicit",
+            params![
+                package.name,
+                package.version,
+                package.installed_at,
+                package.explicit,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Removes the row for `name`, if any, along with its recorded
+    /// dependency edges.
+    pub fn mark_removed(&self, name: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM dependencies WHERE package_id = (SELECT id FROM packages WHERE name = ?1)",
+            params![name],
+        )?;
+        self.conn
+            .execute("DELETE FROM packages WHERE name = ?1", params![name])?;
+        Ok(())
+    }
+
+    /// Replaces the recorded dependency edges for `name` with `deps`.
+    pub fn set_dependencies(&self, name: &str, deps: &[String]) -> Result<()> {
+        let package_id: i64 = self.conn.query_row(
+            "SELECT id FROM packages WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+
+        self.conn.execute(
+            "DELETE FROM dependencies WHERE package_id = ?1",
+            params![package_id],
+        )?;
+
+        for dep in deps {
+            self.conn.execute(
+                "INSERT INTO dependencies (package_id, depends_on) VALUES (?1, ?2)",
+                params![package_id, dep],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns installed, non-explicit packages that are no longer
+    /// referenced as a dependency of any other installed package, including
+    /// ones that only become orphaned once an earlier orphan in the same
+    /// chain is dropped from consideration (e.g. A depends only on B, both
+    /// non-explicit: removing A should also surface B here in one pass).
+    pub fn orphaned_dependencies(&self) -> Result<Vec<String>> {
+        let installed = self.query_installed(false)?;
+        let mut remaining: HashSet<String> =
+            installed.iter().map(|pkg| pkg.name.clone()).collect();
+        let explicit: HashSet<String> = installed
+            .iter()
+            .filter(|pkg| pkg.explicit)
+            .map(|pkg| pkg.name.clone())
+            .collect();
+
+        let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT p.name, d.depends_on FROM dependencies d JOIN packages p ON p.id = d.package_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+            let (name, depends_on) = row?;
+            edges.entry(name).or_default().push(depends_on);
+        }
+
+        let mut orphans = Vec::new();
+        loop {
+            let referenced: HashSet<&String> = remaining
+                .iter()
+                .flat_map(|name| edges.get(name).into_iter().flatten())
+                .collect();
+
+            let newly_orphaned: Vec<String> = remaining
+                .iter()
+                .filter(|name| !explicit.contains(*name) && !referenced.contains(*name))
+                .cloned()
+                .collect();
+
+            if newly_orphaned.is_empty() {
+                break;
+            }
+
+            for name in &newly_orphaned {
+                remaining.remove(name);
+            }
+            orphans.extend(newly_orphaned);
+        }
+
+        orphans.sort();
+        Ok(orphans)
+    }
+
+    /// Returns the installed packages, optionally restricted to those that
+    /// were installed explicitly rather than pulled in as a dependency.
+    pub fn query_installed(&self, explicit_only: bool) -> Result<Vec<InstalledPackage>> {
+        let sql = if explicit_only {
+            "SELECT name, version, installed_at, explicit FROM packages WHERE explicit = 1 ORDER BY name"
+        } else {
+            "SELECT name, version, installed_at, explicit FROM packages ORDER BY name"
+        };
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok(InstalledPackage {
+                name: row.get(0)?,
+                version: row.get(1)?,
+                installed_at: row.get(2)?,
+                explicit: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> Database {
+        let conn = Connection::open_in_memory().unwrap();
+        let db = Database { conn };
+        db.init_schema().unwrap();
+        db
+    }
+
+    fn installed(name: &str, explicit: bool) -> InstalledPackage {
+        InstalledPackage {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            installed_at: 0,
+            explicit,
+        }
+    }
+
+    #[test]
+    fn record_install_inserts_then_updates_on_conflict() {
+        let db = test_db();
+        db.record_install(&installed("app", true)).unwrap();
+
+        let mut upgraded = installed("app", true);
+        upgraded.version = "2.0.0".to_string();
+        db.record_install(&upgraded).unwrap();
+
+        let packages = db.query_installed(false).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].version, "2.0.0");
+    }
+
+    #[test]
+    fn mark_removed_deletes_package_and_its_dependency_edges() {
+        let db = test_db();
+        db.record_install(&installed("app", true)).unwrap();
+        db.set_dependencies("app", &["lib".to_string()]).unwrap();
+
+        db.mark_removed("app").unwrap();
+
+        assert!(db.query_installed(false).unwrap().is_empty());
+        let edges: i64 = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM dependencies", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(edges, 0);
+    }
+
+    #[test]
+    fn query_installed_filters_to_explicit() {
+        let db = test_db();
+        db.record_install(&installed("app", true)).unwrap();
+        db.record_install(&installed("lib", false)).unwrap();
+
+        let explicit = db.query_installed(true).unwrap();
+
+        assert_eq!(explicit.len(), 1);
+        assert_eq!(explicit[0].name, "app");
+    }
+
+    #[test]
+    fn orphaned_dependencies_skips_packages_still_referenced() {
+        let db = test_db();
+        db.record_install(&installed("app", true)).unwrap();
+        db.record_install(&installed("lib", false)).unwrap();
+        db.set_dependencies("app", &["lib".to_string()]).unwrap();
+
+        assert!(db.orphaned_dependencies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn orphaned_dependencies_reaches_a_fixpoint_in_one_call() {
+        let db = test_db();
+        db.record_install(&installed("app", true)).unwrap();
+        db.record_install(&installed("a", false)).unwrap();
+        db.record_install(&installed("b", false)).unwrap();
+        db.set_dependencies("app", &["a".to_string()]).unwrap();
+        db.set_dependencies("a", &["b".to_string()]).unwrap();
+
+        // Removing "app" drops its "app" -> "a" edge, leaving "a"
+        // unreferenced; "b" only becomes unreferenced once "a" is dropped
+        // from consideration within the same call.
+        db.mark_removed("app").unwrap();
+
+        let orphans = db.orphaned_dependencies().unwrap();
+        assert_eq!(orphans, vec!["a".to_string(), "b".to_string()]);
+    }
 }
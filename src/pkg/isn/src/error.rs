@@ -34,3 +34,16 @@ pub enum IsnError {
 }
 
 pub type Result<T> = std::result::Result<T, IsnError>;
+
+impl IsnError {
+    /// The process exit code this error should surface as. Most errors are
+    /// a plain failure (1), but `PackageAlreadyInstalled` gets its own code
+    /// so scripting callers can tell "nothing to do" from a real error
+    /// without parsing stderr.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            IsnError::PackageAlreadyInstalled(_) => 2,
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::error::{IsnError, Result};
+use crate::package::Package;
+
+/// Downloads `package`'s archive from `config.mirrors` into
+/// `config.cache_dir`, trying each mirror in order and falling through to
+/// the next on a network failure. A cached archive whose checksum already
+/// matches is reused without hitting the network.
+pub fn fetch(config: &Config, package: &Package) -> Result<PathBuf> {
+    fs::create_dir_all(&config.cache_dir)?;
+
+    let archive_name = format!("{}-{}.apk", package.name, package.version);
+    let cache_path = config.cache_dir.join(&archive_name);
+
+    if cache_path.exists() && verify(&cache_path, package).is_ok() {
+        return Ok(cache_path);
+    }
+
+    let mut last_error = None;
+
+    for mirror in &config.mirrors {
+        let url = format!("{}/{}", mirror.trim_end_matches('/'), archive_name);
+
+        match download(&url, &cache_path) {
+            Ok(()) => {
+                verify(&cache_path, package)?;
+                return Ok(cache_path);
+            }
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        IsnError::NetworkError(format!("no mirrors configured for package '{}'", package.name))
+    }))
+}
+
+fn download(url: &str, destination: &Path) -> Result<()> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|err| IsnError::NetworkError(format!("{}: {}", url, err)))?;
+
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut body)
+        .map_err(|err| IsnError::NetworkError(format!("{}: {}", url, err)))?;
+
+    let mut file = fs::File::create(destination)?;
+    file.write_all(&body)?;
+
+    Ok(())
+}
+
+/// Confirms the archive at `path` matches `package.size` and, when known,
+/// `package.checksum`. Some package sources (e.g. the local apk database)
+/// don't expose a checksum at all; in that case `package.checksum` is
+/// empty and only the size is checked.
+fn verify(path: &Path, package: &Package) -> Result<()> {
+    let bytes = fs::read(path)?;
+
+    if bytes.len() as u64 != package.size {
+        return Err(IsnError::VerificationError(format!(
+            "expected size {} for '{}', got {}",
+            package.size,
+            package.name,
+            bytes.len()
+        )));
+    }
+
+    if package.checksum.is_empty() {
+        return Ok(());
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    let actual = format!("{:x}", hasher.finalize());
+
+    if actual != package.checksum {
+        return Err(IsnError::VerificationError(format!(
+            "checksum mismatch for '{}': expected {}, got {}",
+            package.name, package.checksum, actual
+        )));
+    }
+
+    Ok(())
+}
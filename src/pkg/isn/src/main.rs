@@ -1,11 +1,16 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 use std::process;
 
 mod commands;
 mod config;
 mod database;
 mod error;
+mod fetch;
 mod package;
+mod parse;
+mod resolver;
+mod ui;
 
 use error::Result;
 
@@ -14,6 +19,10 @@ use error::Result;
 #[command(about = "Kimigayo OS Package Manager", long_about = None)]
 #[command(version)]
 struct Cli {
+    /// Path to an alternate configuration file
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,6 +44,15 @@ enum Commands {
         /// Remove without asking for confirmation
         #[arg(short, long)]
         yes: bool,
+        /// Also remove dependencies that are no longer needed
+        #[arg(short, long)]
+        purge: bool,
+    },
+    /// Remove orphaned dependencies that are no longer needed
+    Autoremove {
+        /// Remove without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Update package database
     Update,
@@ -76,20 +94,29 @@ enum Commands {
 fn main() {
     let cli = Cli::parse();
 
+    let config = match config::Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
     let result = match cli.command {
-        Commands::Install { package, yes } => commands::install(&package, yes),
-        Commands::Remove { package, yes } => commands::remove(&package, yes),
+        Commands::Install { package, yes } => commands::install(&config, &package, yes),
+        Commands::Remove { package, yes, purge } => commands::remove(&config, &package, yes, purge),
+        Commands::Autoremove { yes } => commands::autoremove(&config, yes),
         Commands::Update => commands::update(),
         Commands::Upgrade { yes } => commands::upgrade(yes),
         Commands::Search { query } => commands::search(&query),
         Commands::Info { package } => commands::info(&package),
-        Commands::List { explicit } => commands::list(explicit),
+        Commands::List { explicit } => commands::list(&config, explicit),
         Commands::Verify { package } => commands::verify(&package),
         Commands::SecurityUpdate { yes } => commands::security_update(yes),
     };
 
     if let Err(e) = result {
         eprintln!("Error: {}", e);
-        process::exit(1);
+        process::exit(e.exit_code());
     }
 }
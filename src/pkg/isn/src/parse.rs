@@ -0,0 +1,221 @@
+use std::process::Output;
+
+use crate::package::{InstalledPackage, Package};
+
+/// The outcome of running `apk add`, distinguished beyond a plain
+/// success/failure exit code so callers can tell "nothing to do" from a
+/// real error.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstallOutcome {
+    Installed,
+    AlreadyInstalled,
+    Failed(String),
+}
+
+/// Classifies an `apk add` invocation by inspecting both its exit status
+/// and stdout/stderr for apk's "already installed" marker.
+pub fn classify_install(output: &Output) -> InstallOutcome {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if already_installed(&stdout) || already_installed(&stderr) {
+        return InstallOutcome::AlreadyInstalled;
+    }
+
+    if output.status.success() {
+        InstallOutcome::Installed
+    } else {
+        InstallOutcome::Failed(stderr.into_owned())
+    }
+}
+
+fn already_installed(text: &str) -> bool {
+    text.to_lowercase().contains("already installed")
+}
+
+/// Parses `apk search` output (one `name-version[ - description]` token
+/// per line) into `Package` values. Fields that `apk search` doesn't
+/// report (dependencies, size, checksum) are left empty.
+pub fn parse_search(output: &str) -> Vec<Package> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let (token, description) = match line.split_once(" - ") {
+                Some((token, description)) => (token, description.trim().to_string()),
+                None => (line.trim(), String::new()),
+            };
+            let (name, version) = split_name_version(token)?;
+            Some(Package {
+                name,
+                version,
+                description,
+                dependencies: Vec::new(),
+                size: 0,
+                checksum: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Parses `apk info <package>` output into a single `Package`, reading the
+/// `name-version description:` header line and taking the first non-empty
+/// line after it as the description.
+pub fn parse_info(output: &str) -> Option<Package> {
+    let mut lines = output.lines();
+    let header = lines.find(|line| line.contains("description:"))?;
+    let token = header.split_whitespace().next()?;
+    let (name, version) = split_name_version(token)?;
+    let description = lines
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+
+    Some(Package {
+        name,
+        version,
+        description,
+        dependencies: Vec::new(),
+        size: 0,
+        checksum: String::new(),
+    })
+}
+
+/// Parses `apk list --installed` output (one `name-version arch {...} ...`
+/// line per package) into `InstalledPackage` values. `installed_at` and
+/// `explicit` aren't reported by apk, so they're left as defaults; the
+/// database is the source of truth for those.
+pub fn parse_installed_list(output: &str) -> Vec<InstalledPackage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let token = line.split_whitespace().next()?;
+            let (name, version) = split_name_version(token)?;
+            Some(InstalledPackage {
+                name,
+                version,
+                installed_at: 0,
+                explicit: false,
+            })
+        })
+        .collect()
+}
+
+/// Splits an apk `name-version-release` token into `(name, version)`.
+/// apk doesn't delimit the two with a stable separator and package names
+/// may themselves contain hyphens, so this treats the first `-` followed
+/// by a digit as the start of the version.
+fn split_name_version(token: &str) -> Option<(String, String)> {
+    let bytes = token.as_bytes();
+    for i in 0..bytes.len() {
+        if bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+            return Some((token[..i].to_string(), token[i + 1..].to_string()));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn output(status: i32, stdout: &str, stderr: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(status),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn splits_simple_name_version() {
+        assert_eq!(
+            split_name_version("curl-8.5.0"),
+            Some(("curl".to_string(), "8.5.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn splits_hyphenated_name_before_first_numeric_component() {
+        assert_eq!(
+            split_name_version("linux-firmware-20240811"),
+            Some(("linux-firmware".to_string(), "20240811".to_string()))
+        );
+    }
+
+    #[test]
+    fn splits_name_version_release() {
+        assert_eq!(
+            split_name_version("openssl-3.1.4-r0"),
+            Some(("openssl".to_string(), "3.1.4-r0".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_token_with_no_version() {
+        assert_eq!(split_name_version("busybox"), None);
+    }
+
+    #[test]
+    fn classify_install_reports_installed_on_success() {
+        let out = output(0, "", "");
+        assert_eq!(classify_install(&out), InstallOutcome::Installed);
+    }
+
+    #[test]
+    fn classify_install_detects_already_installed_regardless_of_case() {
+        let out = output(0, "", "Already INSTALLED: curl-8.5.0\n");
+        assert_eq!(classify_install(&out), InstallOutcome::AlreadyInstalled);
+    }
+
+    #[test]
+    fn classify_install_reports_failure_with_stderr() {
+        let out = output(1, "", "ERROR: unable to select packages\n");
+        assert_eq!(
+            classify_install(&out),
+            InstallOutcome::Failed("ERROR: unable to select packages\n".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_search_output_with_and_without_description() {
+        let text = "curl-8.5.0 - command line tool for transferring data\nbusybox-1.36.1\n";
+        let packages = parse_search(text);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "curl");
+        assert_eq!(packages[0].version, "8.5.0");
+        assert_eq!(
+            packages[0].description,
+            "command line tool for transferring data"
+        );
+        assert_eq!(packages[1].name, "busybox");
+        assert_eq!(packages[1].version, "1.36.1");
+        assert!(packages[1].description.is_empty());
+    }
+
+    #[test]
+    fn parses_info_output() {
+        let text = "curl-8.5.0 description:\nA command line tool for transferring data\n";
+        let pkg = parse_info(text).unwrap();
+
+        assert_eq!(pkg.name, "curl");
+        assert_eq!(pkg.version, "8.5.0");
+        assert_eq!(pkg.description, "A command line tool for transferring data");
+    }
+
+    #[test]
+    fn parses_installed_list() {
+        let text = "curl-8.5.0 x86_64 {curl} (MIT)\nbusybox-1.36.1 x86_64 {busybox} (GPL-2.0)\n";
+        let packages = parse_installed_list(text);
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "curl");
+        assert_eq!(packages[0].version, "8.5.0");
+        assert_eq!(packages[1].name, "busybox");
+        assert_eq!(packages[1].version, "1.36.1");
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::{IsnError, Result};
+use crate::package::Package;
+
+/// The result of resolving a set of requested packages: an install order
+/// (dependencies before dependents) plus which of those packages were
+/// explicitly requested versus pulled in transitively.
+pub struct InstallPlan {
+    pub order: Vec<String>,
+    pub explicit: HashSet<String>,
+}
+
+/// Resolves the dependency closure of `requested` against `available`
+/// (known packages keyed by name) and produces an install order via Kahn's
+/// algorithm. Packages already present in `installed` are skipped, whether
+/// they were requested directly or pulled in as a dependency — callers
+/// that need to flip an already-installed package to `explicit` should do
+/// so directly rather than relying on `order` to contain it. Returns
+/// `IsnError::DependencyError` if the closure contains a cycle.
+pub fn resolve(
+    requested: &[String],
+    available: &HashMap<String, Package>,
+    installed: &HashSet<String>,
+) -> Result<InstallPlan> {
+    let mut closure: HashSet<String> = HashSet::new();
+    let mut stack: Vec<String> = Vec::new();
+
+    for name in requested {
+        if installed.contains(name) {
+            continue;
+        }
+        closure.insert(name.clone());
+        stack.push(name.clone());
+    }
+
+    while let Some(name) = stack.pop() {
+        let Some(pkg) = available.get(&name) else {
+            continue;
+        };
+        for dep in &pkg.dependencies {
+            if installed.contains(dep) || closure.contains(dep) {
+                continue;
+            }
+            closure.insert(dep.clone());
+            stack.push(dep.clone());
+        }
+    }
+
+    let mut in_degree: HashMap<String, usize> =
+        closure.iter().map(|name| (name.clone(), 0)).collect();
+    let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+
+    for name in &closure {
+        let deps = available
+            .get(name)
+            .map(|pkg| pkg.dependencies.clone())
+            .unwrap_or_default();
+
+        for dep in deps {
+            if !closure.contains(&dep) {
+                continue;
+            }
+            *in_degree.get_mut(name).unwrap() += 1;
+            dependents.entry(dep).or_default().push(name.clone());
+        }
+    }
+
+    let mut ready: Vec<String> = in_degree
+        .iter()
+        .filter(|(_, &degree)| degree == 0)
+        .map(|(name, _)| name.clone())
+        .collect();
+    ready.sort();
+    let mut queue: VecDeque<String> = ready.into();
+
+    let mut order = Vec::with_capacity(closure.len());
+    while let Some(name) = queue.pop_front() {
+        if let Some(dependents) = dependents.get(&name) {
+            for dependent in dependents {
+                let degree = in_degree.get_mut(dependent).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(dependent.clone());
+                }
+            }
+        }
+        order.push(name);
+    }
+
+    if order.len() < closure.len() {
+        let resolved: HashSet<&String> = order.iter().collect();
+        let mut cycle: Vec<String> = closure
+            .iter()
+            .filter(|name| !resolved.contains(name))
+            .cloned()
+            .collect();
+        cycle.sort();
+        return Err(IsnError::DependencyError(format!(
+            "circular dependency among: {}",
+            cycle.join(", ")
+        )));
+    }
+
+    Ok(InstallPlan {
+        order,
+        explicit: requested.iter().cloned().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, dependencies: &[&str]) -> Package {
+        Package {
+            name: name.to_string(),
+            version: String::new(),
+            description: String::new(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            size: 0,
+            checksum: String::new(),
+        }
+    }
+
+    fn names(strings: &[&str]) -> Vec<String> {
+        strings.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn orders_dependencies_before_dependents() {
+        let mut available = HashMap::new();
+        available.insert("app".to_string(), package("app", &["lib"]));
+        available.insert("lib".to_string(), package("lib", &[]));
+
+        let plan = resolve(&names(&["app"]), &available, &HashSet::new()).unwrap();
+
+        assert_eq!(plan.order, names(&["lib", "app"]));
+        assert_eq!(plan.explicit, names(&["app"]).into_iter().collect());
+    }
+
+    #[test]
+    fn skips_already_satisfied_dependencies() {
+        let mut available = HashMap::new();
+        available.insert("app".to_string(), package("app", &["lib"]));
+        available.insert("lib".to_string(), package("lib", &[]));
+
+        let installed: HashSet<String> = names(&["lib"]).into_iter().collect();
+        let plan = resolve(&names(&["app"]), &available, &installed).unwrap();
+
+        assert_eq!(plan.order, names(&["app"]));
+    }
+
+    #[test]
+    fn skips_already_installed_requested_packages() {
+        let mut available = HashMap::new();
+        available.insert("app".to_string(), package("app", &[]));
+
+        let installed: HashSet<String> = names(&["app"]).into_iter().collect();
+        let plan = resolve(&names(&["app"]), &available, &installed).unwrap();
+
+        assert!(plan.order.is_empty());
+        assert_eq!(plan.explicit, names(&["app"]).into_iter().collect());
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let mut available = HashMap::new();
+        available.insert("a".to_string(), package("a", &["b"]));
+        available.insert("b".to_string(), package("b", &["a"]));
+
+        let err = resolve(&names(&["a"]), &available, &HashSet::new()).unwrap_err();
+
+        match err {
+            IsnError::DependencyError(message) => {
+                assert!(message.contains('a'));
+                assert!(message.contains('b'));
+            }
+            other => panic!("expected DependencyError, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Prompts the user with `message [y/N]` and reads a yes/no answer from
+/// stdin. Returns `true` without prompting when `auto_yes` is set (the
+/// `--yes` flag).
+pub fn confirm(message: &str, auto_yes: bool) -> bool {
+    if auto_yes {
+        return true;
+    }
+
+    print!("{} [y/N] ", message);
+    let _ = io::stdout().flush();
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Starts an animated spinner showing `message` (e.g. "Installing…").
+/// Stop it with `ProgressBar::finish_and_clear` once the backend command
+/// has returned.
+pub fn spinner(message: &str) -> ProgressBar {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::with_template("{spinner:.cyan} {msg}")
+            .unwrap()
+            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ "),
+    );
+    pb.set_message(message.to_string());
+    pb.enable_steady_tick(Duration::from_millis(100));
+    pb
+}
+
+/// Prints a green checkmark followed by `message`.
+pub fn success(message: &str) {
+    println!("{} {}", "✓".green(), message);
+}
+
+/// Prints `message` to stderr in red.
+pub fn error(message: &str) {
+    eprintln!("{}", message.red());
+}
+
+/// Prints `message` in yellow.
+pub fn warning(message: &str) {
+    println!("{}", message.yellow());
+}